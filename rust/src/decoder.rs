@@ -0,0 +1,473 @@
+//! Incremental decoder for the C2PA Text Manifest Wrapper.
+//!
+//! [`extract_manifest`](crate::extract_manifest) needs the whole document in
+//! memory before it can scan it for a wrapper. [`WrapperDecoder`] instead
+//! accepts `&str` chunks one at a time via [`WrapperDecoder::feed`], so large
+//! or streamed text can be scanned without buffering it all up front.
+
+use crate::format;
+use crate::{byte_to_vs, vs_to_byte, Error, MAGIC, MAGIC_AND_VERSION_SIZE, ZWNBSP};
+
+/// Result of feeding a chunk to a [`WrapperDecoder`].
+#[derive(Debug)]
+pub enum DecodeState {
+    /// No wrapper has been fully decoded yet; feed more chunks.
+    NeedMore,
+    /// A wrapper was found and fully decoded. `clean_text` reflects
+    /// everything seen so far across this and prior chunks with the wrapper
+    /// removed — not yet anything from chunks fed after this call returns.
+    /// Call [`WrapperDecoder::finish`] once the stream ends to get the fully
+    /// reconstructed clean text.
+    Complete(crate::ExtractionResult),
+}
+
+/// A wrapper candidate currently being accumulated, starting from a ZWNBSP
+/// the decoder has seen but not yet resolved as valid or invalid.
+struct Candidate {
+    start_byte: usize,
+    bytes: Vec<u8>,
+}
+
+impl Candidate {
+    fn new(start_byte: usize) -> Self {
+        Self {
+            start_byte,
+            bytes: Vec::new(),
+        }
+    }
+}
+
+struct CompletedWrapper {
+    manifest: Vec<u8>,
+    offset: usize,
+    length: usize,
+}
+
+/// Incremental state machine that extracts a C2PA Text Manifest Wrapper from
+/// a stream of `&str` chunks, without buffering the whole document.
+///
+/// Internally this mirrors the scan [`extract_manifest`](crate::extract_manifest)
+/// does over [`ZWNBSP`] and the bytes that follow it, but is able to suspend
+/// between chunks. Because chunks are `&str` (always valid UTF-8 on their
+/// own), no byte ever needs to be carried across a chunk boundary to
+/// complete a split char — only the decoder's scan position and the
+/// in-progress candidate do. Like `extract_manifest`, header parsing and
+/// checksum validation are delegated to whatever [`WrapperReader`](crate::WrapperReader)
+/// is registered for the version byte found after MAGIC
+/// ([`format::with_reader_for_version`]), so a format registered via
+/// [`register_format`](crate::format::register_format) is recognized here
+/// too, not just by the single-shot scan.
+pub struct WrapperDecoder {
+    byte_offset: usize,
+    clean_text: String,
+    candidate: Option<Candidate>,
+    completed: Option<CompletedWrapper>,
+    /// Set right after a candidate completes, while the decoder is still
+    /// swallowing the rest of its contiguous variation-selector run (bytes
+    /// beyond the declared body that were never part of the wrapper).
+    /// Mirrors `extract_manifest`'s greedy scan, which consumes the whole
+    /// run before deciding where the wrapper ends.
+    discarding_trailing_vs: bool,
+    trailing_discard_bytes: usize,
+}
+
+impl WrapperDecoder {
+    /// Create a new, empty decoder.
+    pub fn new() -> Self {
+        Self {
+            byte_offset: 0,
+            clean_text: String::new(),
+            candidate: None,
+            completed: None,
+            discarding_trailing_vs: false,
+            trailing_discard_bytes: 0,
+        }
+    }
+
+    /// Feed the next chunk of text into the decoder.
+    ///
+    /// A wrapper completing partway through `chunk` doesn't end the scan:
+    /// the rest of the chunk is still processed, so text after the wrapper
+    /// is preserved in `clean_text` and a second wrapper later in the same
+    /// chunk is still caught as [`Error::MultipleWrappers`].
+    pub fn feed(&mut self, chunk: &str) -> Result<DecodeState, Error> {
+        let mut completed = false;
+        for c in chunk.chars() {
+            if self.feed_char(c)?.is_some() {
+                completed = true;
+            }
+        }
+        if completed {
+            Ok(DecodeState::Complete(self.snapshot()))
+        } else {
+            Ok(DecodeState::NeedMore)
+        }
+    }
+
+    /// Build an [`ExtractionResult`](crate::ExtractionResult) reflecting the
+    /// decoder's state right now, without consuming it.
+    fn snapshot(&self) -> crate::ExtractionResult {
+        match &self.completed {
+            Some(completed) => crate::ExtractionResult {
+                manifest: Some(completed.manifest.clone()),
+                clean_text: self.clean_text.clone(),
+                offset: Some(completed.offset),
+                length: Some(completed.length),
+            },
+            None => crate::ExtractionResult {
+                manifest: None,
+                clean_text: self.clean_text.clone(),
+                offset: None,
+                length: None,
+            },
+        }
+    }
+
+    /// Consume the decoder once the stream has ended, returning the final
+    /// result: the manifest (if one was found), and the full clean text with
+    /// the wrapper (if any) removed.
+    ///
+    /// Any still-unresolved candidate (a trailing ZWNBSP, or a cut-off
+    /// header/body that never completed) is flushed back into `clean_text`
+    /// verbatim via [`abandon_candidate`](Self::abandon_candidate), matching
+    /// how [`extract_manifest`](crate::extract_manifest) treats the same
+    /// input: a ZWNBSP is ordinary text until it's resolved into a complete
+    /// wrapper.
+    pub fn finish(mut self) -> crate::ExtractionResult {
+        self.abandon_candidate();
+        if self.discarding_trailing_vs {
+            self.flush_trailing_discard();
+        }
+        match self.completed {
+            Some(completed) => crate::ExtractionResult {
+                manifest: Some(completed.manifest),
+                clean_text: self.clean_text,
+                offset: Some(completed.offset),
+                length: Some(completed.length),
+            },
+            None => crate::ExtractionResult {
+                manifest: None,
+                clean_text: self.clean_text,
+                offset: None,
+                length: None,
+            },
+        }
+    }
+
+    fn feed_char(&mut self, c: char) -> Result<Option<DecodeState>, Error> {
+        let char_len = c.len_utf8();
+
+        if self.candidate.is_none() {
+            if self.discarding_trailing_vs {
+                if vs_to_byte(c).is_some() {
+                    self.trailing_discard_bytes += char_len;
+                    self.byte_offset += char_len;
+                    return Ok(None);
+                }
+                self.discarding_trailing_vs = false;
+                self.flush_trailing_discard();
+            }
+            if c == ZWNBSP {
+                self.candidate = Some(Candidate::new(self.byte_offset));
+            } else {
+                self.clean_text.push(c);
+            }
+            self.byte_offset += char_len;
+            return Ok(None);
+        }
+
+        let Some(b) = vs_to_byte(c) else {
+            // `c` isn't a variation selector: the candidate is unresolved, so
+            // give up on it and start fresh from `c`.
+            self.abandon_candidate();
+            if c == ZWNBSP {
+                self.candidate = Some(Candidate::new(self.byte_offset));
+            } else {
+                self.clean_text.push(c);
+            }
+            self.byte_offset += char_len;
+            return Ok(None);
+        };
+
+        let candidate = self.candidate.as_mut().unwrap();
+        candidate.bytes.push(b);
+        self.byte_offset += char_len;
+
+        if candidate.bytes.len() < MAGIC_AND_VERSION_SIZE {
+            return Ok(None);
+        }
+        if candidate.bytes[0..8] != *MAGIC {
+            self.abandon_candidate();
+            return Ok(None);
+        }
+
+        let version = candidate.bytes[8];
+        let decode_result =
+            format::with_reader_for_version(version, |reader| reader.try_decode(&candidate.bytes));
+
+        match decode_result {
+            Some(Ok(decoded)) => self.complete_candidate(decoded).map(Some),
+            // Header or body not fully buffered yet; keep accumulating.
+            Some(Err(Error::TooShort)) | Some(Err(Error::Truncated)) => Ok(None),
+            // A real error (e.g. a checksum the declared body doesn't match)
+            // rather than an incomplete one: surface it like extract_manifest
+            // does, instead of silently treating the candidate as plain text.
+            Some(Err(err @ Error::ChecksumMismatch { .. })) => {
+                self.candidate = None;
+                Err(err)
+            }
+            // No registered reader recognizes this version, or this format's
+            // reader rejected it outright: not a valid wrapper.
+            Some(Err(_)) | None => {
+                self.abandon_candidate();
+                Ok(None)
+            }
+        }
+    }
+
+    /// Give up on the in-progress candidate: its ZWNBSP and any
+    /// variation-selector bytes collected so far were never a valid wrapper,
+    /// so re-emit them as ordinary text. Round-tripping through
+    /// [`byte_to_vs`] is exact, since it's the inverse of [`vs_to_byte`].
+    fn abandon_candidate(&mut self) {
+        if let Some(candidate) = self.candidate.take() {
+            self.clean_text.push(ZWNBSP);
+            for b in candidate.bytes {
+                self.clean_text.push(byte_to_vs(b));
+            }
+        }
+    }
+
+    /// Fold bytes swallowed from the tail of a completed wrapper's
+    /// variation-selector run into the just-completed wrapper's reported
+    /// length, then reset the counter.
+    fn flush_trailing_discard(&mut self) {
+        if let Some(completed) = self.completed.as_mut() {
+            completed.length += self.trailing_discard_bytes;
+        }
+        self.trailing_discard_bytes = 0;
+    }
+
+    /// Finalize a candidate the registered [`WrapperReader`](crate::WrapperReader)
+    /// has just fully decoded.
+    fn complete_candidate(&mut self, decoded: format::DecodedWrapper) -> Result<DecodeState, Error> {
+        let candidate = self.candidate.take().unwrap();
+
+        if self.completed.is_some() {
+            return Err(Error::MultipleWrappers);
+        }
+
+        let offset = candidate.start_byte;
+        let length = self.byte_offset - offset;
+        let body = decoded.manifest;
+        self.completed = Some(CompletedWrapper {
+            manifest: body.clone(),
+            offset,
+            length,
+        });
+        // The candidate's bytes may run on past the declared body (extra
+        // contiguous variation-selector chars that were never part of the
+        // wrapper); swallow them the same way extract_manifest's greedy scan
+        // does, instead of leaking them into clean_text as if they were
+        // ordinary characters.
+        self.discarding_trailing_vs = true;
+
+        Ok(DecodeState::Complete(crate::ExtractionResult {
+            manifest: Some(body),
+            clean_text: self.clean_text.clone(),
+            offset: Some(offset),
+            length: Some(length),
+        }))
+    }
+}
+
+impl Default for WrapperDecoder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{WrapperCreator, WrapperReader};
+
+    #[test]
+    fn test_single_wrapper_across_multiple_chunks() {
+        let wrapper = crate::encode_wrapper(b"hello manifest");
+        let full = format!("before{}after", wrapper);
+
+        let mut decoder = WrapperDecoder::new();
+        let mid = full.len() / 2;
+        // Split on a char boundary.
+        let mid = (0..=mid).rev().find(|&i| full.is_char_boundary(i)).unwrap();
+        let state1 = decoder.feed(&full[..mid]).unwrap();
+        assert!(matches!(state1, DecodeState::NeedMore) || matches!(state1, DecodeState::Complete(_)));
+        let state2 = decoder.feed(&full[mid..]).unwrap();
+        let result = match state2 {
+            DecodeState::Complete(result) => result,
+            DecodeState::NeedMore => decoder.finish(),
+        };
+        assert_eq!(result.manifest, Some(b"hello manifest".to_vec()));
+        assert_eq!(result.clean_text, "beforeafter");
+    }
+
+    #[test]
+    fn test_trailing_text_after_wrapper_in_same_chunk_is_preserved() {
+        let wrapper = crate::encode_wrapper(b"m");
+        let chunk = format!("before{}AFTERTEXT", wrapper);
+
+        let mut decoder = WrapperDecoder::new();
+        let state = decoder.feed(&chunk).unwrap();
+        match state {
+            DecodeState::Complete(result) => {
+                assert_eq!(result.manifest, Some(b"m".to_vec()));
+                // The chunk's trailing text, after the wrapper completed,
+                // must already be reflected here rather than dropped.
+                assert_eq!(result.clean_text, "beforeAFTERTEXT");
+            }
+            DecodeState::NeedMore => panic!("expected Complete"),
+        }
+
+        let result = decoder.finish();
+        assert_eq!(result.clean_text, "beforeAFTERTEXT");
+    }
+
+    #[test]
+    fn test_trailing_lone_zwnbsp_is_preserved_on_finish() {
+        // A ZWNBSP with nothing (or nothing resolvable) after it at EOF is
+        // ordinary text, not a dropped wrapper attempt — matching what
+        // extract_manifest does with the same input.
+        let mut decoder = WrapperDecoder::new();
+        decoder.feed("hello\u{feff}").unwrap();
+        let result = decoder.finish();
+        assert_eq!(result.clean_text, "hello\u{feff}");
+        assert_eq!(result.manifest, None);
+    }
+
+    #[test]
+    fn test_truncated_header_is_preserved_on_finish() {
+        // A cut-off header (here just the ZWNBSP and the first MAGIC byte)
+        // must round-trip back into clean_text rather than vanishing.
+        let wrapper = crate::encode_wrapper(b"never completes");
+        let cut = 10usize.min(wrapper.len());
+        let cut = (0..=cut).rev().find(|&i| wrapper.is_char_boundary(i)).unwrap();
+        let truncated = &wrapper[..cut];
+
+        let mut decoder = WrapperDecoder::new();
+        decoder.feed(truncated).unwrap();
+        let result = decoder.finish();
+        assert_eq!(result.clean_text, truncated);
+        assert_eq!(result.manifest, None);
+    }
+
+    #[test]
+    fn test_extra_vs_byte_after_body_is_discarded_not_leaked() {
+        // A contiguous VS-encoded byte directly after a complete wrapper,
+        // with no ordinary character separating them, is part of the same
+        // run extract_manifest would greedily consume and discard — it must
+        // not leak into clean_text as if it were real (invisible) text.
+        let wrapper = crate::encode_wrapper(b"m");
+        let chunk = format!("{}{}", wrapper, crate::byte_to_vs(0));
+
+        let mut decoder = WrapperDecoder::new();
+        let state = decoder.feed(&chunk).unwrap();
+        let result = match state {
+            DecodeState::Complete(result) => result,
+            DecodeState::NeedMore => panic!("expected Complete"),
+        };
+        assert_eq!(result.manifest, Some(b"m".to_vec()));
+        assert_eq!(result.clean_text, "");
+
+        let reference = crate::extract_manifest(&chunk).unwrap();
+        assert_eq!(result.clean_text, reference.clean_text);
+    }
+
+    #[test]
+    fn test_registered_custom_format_is_decoded_like_extract_manifest() {
+        // WrapperDecoder dispatches through the same format registry as
+        // extract_manifest, so a format an embedder registers via
+        // format::register_format is recognized by both, not just the
+        // single-shot scan.
+        const CUSTOM_VERSION: u8 = 0xFC;
+
+        struct CustomFormat;
+
+        impl WrapperCreator for CustomFormat {
+            fn version(&self) -> u8 {
+                CUSTOM_VERSION
+            }
+
+            fn encode(&self, manifest: &[u8]) -> String {
+                let mut out = String::new();
+                out.push(ZWNBSP);
+                for &b in MAGIC {
+                    out.push(byte_to_vs(b));
+                }
+                out.push(byte_to_vs(CUSTOM_VERSION));
+                for &b in &(manifest.len() as u32).to_be_bytes() {
+                    out.push(byte_to_vs(b));
+                }
+                for &b in manifest {
+                    out.push(byte_to_vs(b));
+                }
+                out
+            }
+
+            fn header_len(&self, _manifest: &[u8]) -> usize {
+                MAGIC_AND_VERSION_SIZE + 4
+            }
+        }
+
+        impl WrapperReader for CustomFormat {
+            fn version(&self) -> u8 {
+                CUSTOM_VERSION
+            }
+
+            fn try_decode(&self, bytes: &[u8]) -> Result<format::DecodedWrapper, Error> {
+                let header_len = MAGIC_AND_VERSION_SIZE + 4;
+                if bytes.len() < header_len {
+                    return Err(Error::TooShort);
+                }
+                if bytes[0..8] != *MAGIC || bytes[8] != CUSTOM_VERSION {
+                    return Err(Error::InvalidMagic);
+                }
+                let declared_length = u32::from_be_bytes(
+                    bytes[MAGIC_AND_VERSION_SIZE..header_len].try_into().unwrap(),
+                ) as usize;
+                if bytes.len() < header_len + declared_length {
+                    return Err(Error::Truncated);
+                }
+                Ok(format::DecodedWrapper {
+                    manifest: bytes[header_len..header_len + declared_length].to_vec(),
+                    len_written: header_len + declared_length,
+                })
+            }
+        }
+
+        format::register_format(Box::new(CustomFormat));
+
+        let wrapper = CustomFormat.encode(b"toy manifest");
+        let chunk = format!("before{}after", wrapper);
+
+        let mut decoder = WrapperDecoder::new();
+        let state = decoder.feed(&chunk).unwrap();
+        let result = match state {
+            DecodeState::Complete(result) => result,
+            DecodeState::NeedMore => panic!("expected Complete"),
+        };
+        assert_eq!(result.manifest, Some(b"toy manifest".to_vec()));
+        assert_eq!(result.clean_text, "beforeafter");
+    }
+
+    #[test]
+    fn test_multiple_wrappers_in_one_chunk_is_detected() {
+        let wrapper1 = crate::encode_wrapper(b"one");
+        let wrapper2 = crate::encode_wrapper(b"two");
+        let chunk = format!("before{}middle{}after", wrapper1, wrapper2);
+
+        let mut decoder = WrapperDecoder::new();
+        let err = decoder.feed(&chunk).unwrap_err();
+        assert!(matches!(err, Error::MultipleWrappers));
+    }
+}
@@ -0,0 +1,388 @@
+//! Pluggable registry of wrapper wire formats.
+//!
+//! [`encode_wrapper`](crate::encode_wrapper) and
+//! [`extract_manifest`](crate::extract_manifest) used to hardcode a single
+//! MAGIC/VERSION/layout, so supporting another on-the-wire encoding meant
+//! forking them. [`WrapperFormat`] splits a format into a creator side
+//! ([`WrapperCreator`]) and a reader side ([`WrapperReader`]), keyed by
+//! version byte, so [`extract_manifest`](crate::extract_manifest) can
+//! dispatch to whichever reader matches the version found after MAGIC
+//! without its core scan loop knowing about individual layouts.
+//!
+//! The dispatch table is a process-wide [registry](with_reader_for_version)
+//! seeded with this crate's own v1/v2/v3 formats. Embedders add their own
+//! wire format with [`register_format`] and `extract_manifest` picks it up
+//! immediately — no need to fork the scan loop or reimplement its lookup.
+
+use crate::{
+    byte_to_vs, checksum16, encode_varint, resolve_header, Error, HeaderLayout,
+    CHECKSUM_FIELD_SIZE, FIXED_LENGTH_FIELD_SIZE, MAGIC, MAGIC_AND_VERSION_SIZE, VERSION,
+    VERSION_1, VERSION_2, ZWNBSP,
+};
+use std::sync::{Mutex, OnceLock};
+
+/// A wrapper successfully decoded by a [`WrapperReader`].
+pub struct DecodedWrapper {
+    /// The extracted manifest body.
+    pub manifest: Vec<u8>,
+    /// Total number of bytes consumed, from the start of MAGIC through the
+    /// end of the manifest body.
+    pub len_written: usize,
+}
+
+/// The creator half of a [`WrapperFormat`]: turns manifest bytes into a
+/// complete wrapper string (ZWNBSP + header + body).
+pub trait WrapperCreator {
+    /// Version byte this format writes.
+    fn version(&self) -> u8;
+
+    /// Encode `manifest` into a complete wrapper string.
+    fn encode(&self, manifest: &[u8]) -> String;
+
+    /// Number of header bytes (MAGIC through whatever length/checksum
+    /// fields this format uses) that precede the manifest body when
+    /// encoding `manifest`.
+    fn header_len(&self, manifest: &[u8]) -> usize;
+}
+
+/// The reader half of a [`WrapperFormat`]: recognizes and decodes its own
+/// header and body from already variation-selector-decoded bytes, i.e. the
+/// bytes that followed a ZWNBSP.
+pub trait WrapperReader {
+    /// Version byte this format reads.
+    fn version(&self) -> u8;
+
+    /// Try to decode a wrapper of this format from `bytes` (MAGIC onward).
+    /// Returns `Err` if `bytes` isn't a complete, valid wrapper of this
+    /// format.
+    fn try_decode(&self, bytes: &[u8]) -> Result<DecodedWrapper, Error>;
+}
+
+/// A registered wrapper wire format: both the creator and reader halves for
+/// a single version byte.
+pub trait WrapperFormat: WrapperCreator + WrapperReader {}
+
+impl<T: WrapperCreator + WrapperReader> WrapperFormat for T {}
+
+/// Shared decode path for every format: resolve the header via
+/// [`resolve_header`], slice out the declared body, and check the checksum
+/// for every version except v1 (which has none).
+fn decode_with_resolver(version: u8, bytes: &[u8]) -> Result<DecodedWrapper, Error> {
+    if bytes.len() < MAGIC_AND_VERSION_SIZE || bytes[0..8] != *MAGIC || bytes[8] != version {
+        return Err(Error::InvalidMagic);
+    }
+    match resolve_header(version, &bytes[MAGIC_AND_VERSION_SIZE..]) {
+        HeaderLayout::Known {
+            header_size,
+            declared_length,
+        } => {
+            let body_len = declared_length as usize;
+            if bytes.len() < header_size + body_len {
+                return Err(Error::Truncated);
+            }
+            let body = bytes[header_size..header_size + body_len].to_vec();
+
+            if version != VERSION_1 {
+                let checksum_offset = header_size - CHECKSUM_FIELD_SIZE;
+                let expected =
+                    u16::from_be_bytes([bytes[checksum_offset], bytes[checksum_offset + 1]]);
+                let found = checksum16(&body);
+                if expected != found {
+                    return Err(Error::ChecksumMismatch { expected, found });
+                }
+            }
+
+            Ok(DecodedWrapper {
+                manifest: body,
+                len_written: header_size + body_len,
+            })
+        }
+        HeaderLayout::Incomplete => Err(Error::TooShort),
+        HeaderLayout::Invalid => Err(Error::UnsupportedVersion),
+    }
+}
+
+/// v1: a fixed 4-byte big-endian length, no checksum.
+pub struct V1Format;
+
+impl WrapperCreator for V1Format {
+    fn version(&self) -> u8 {
+        VERSION_1
+    }
+
+    fn encode(&self, manifest: &[u8]) -> String {
+        let len = manifest.len() as u32;
+        let mut out = String::with_capacity(1 + self.header_len(manifest) + manifest.len());
+        out.push(ZWNBSP);
+        for &b in MAGIC {
+            out.push(byte_to_vs(b));
+        }
+        out.push(byte_to_vs(VERSION_1));
+        for &b in &len.to_be_bytes() {
+            out.push(byte_to_vs(b));
+        }
+        for &b in manifest {
+            out.push(byte_to_vs(b));
+        }
+        out
+    }
+
+    fn header_len(&self, _manifest: &[u8]) -> usize {
+        MAGIC_AND_VERSION_SIZE + FIXED_LENGTH_FIELD_SIZE
+    }
+}
+
+impl WrapperReader for V1Format {
+    fn version(&self) -> u8 {
+        VERSION_1
+    }
+
+    fn try_decode(&self, bytes: &[u8]) -> Result<DecodedWrapper, Error> {
+        decode_with_resolver(VERSION_1, bytes)
+    }
+}
+
+/// v2: a fixed 4-byte big-endian length, then a 2-byte checksum.
+pub struct V2Format;
+
+impl WrapperCreator for V2Format {
+    fn version(&self) -> u8 {
+        VERSION_2
+    }
+
+    fn encode(&self, manifest: &[u8]) -> String {
+        let len = manifest.len() as u32;
+        let checksum = checksum16(manifest);
+        let mut out = String::with_capacity(1 + self.header_len(manifest) + manifest.len());
+        out.push(ZWNBSP);
+        for &b in MAGIC {
+            out.push(byte_to_vs(b));
+        }
+        out.push(byte_to_vs(VERSION_2));
+        for &b in &len.to_be_bytes() {
+            out.push(byte_to_vs(b));
+        }
+        out.push(byte_to_vs(((checksum >> 8) & 0xFF) as u8));
+        out.push(byte_to_vs((checksum & 0xFF) as u8));
+        for &b in manifest {
+            out.push(byte_to_vs(b));
+        }
+        out
+    }
+
+    fn header_len(&self, _manifest: &[u8]) -> usize {
+        MAGIC_AND_VERSION_SIZE + FIXED_LENGTH_FIELD_SIZE + CHECKSUM_FIELD_SIZE
+    }
+}
+
+impl WrapperReader for V2Format {
+    fn version(&self) -> u8 {
+        VERSION_2
+    }
+
+    fn try_decode(&self, bytes: &[u8]) -> Result<DecodedWrapper, Error> {
+        decode_with_resolver(VERSION_2, bytes)
+    }
+}
+
+/// v3 (current): an EBML-style [`encode_varint`]/[`decode_varint`] length,
+/// then a 2-byte checksum.
+pub struct V3Format;
+
+impl WrapperCreator for V3Format {
+    fn version(&self) -> u8 {
+        VERSION
+    }
+
+    fn encode(&self, manifest: &[u8]) -> String {
+        let checksum = checksum16(manifest);
+        let length_field = encode_varint(manifest.len() as u32);
+        let mut out = String::with_capacity(
+            1 + MAGIC_AND_VERSION_SIZE
+                + length_field.len()
+                + CHECKSUM_FIELD_SIZE
+                + manifest.len(),
+        );
+        out.push(ZWNBSP);
+        for &b in MAGIC {
+            out.push(byte_to_vs(b));
+        }
+        out.push(byte_to_vs(VERSION));
+        for &b in &length_field {
+            out.push(byte_to_vs(b));
+        }
+        out.push(byte_to_vs(((checksum >> 8) & 0xFF) as u8));
+        out.push(byte_to_vs((checksum & 0xFF) as u8));
+        for &b in manifest {
+            out.push(byte_to_vs(b));
+        }
+        out
+    }
+
+    fn header_len(&self, manifest: &[u8]) -> usize {
+        MAGIC_AND_VERSION_SIZE + encode_varint(manifest.len() as u32).len() + CHECKSUM_FIELD_SIZE
+    }
+}
+
+impl WrapperReader for V3Format {
+    fn version(&self) -> u8 {
+        VERSION
+    }
+
+    fn try_decode(&self, bytes: &[u8]) -> Result<DecodedWrapper, Error> {
+        decode_with_resolver(VERSION, bytes)
+    }
+}
+
+/// Process-wide dispatch table for [`extract_manifest`](crate::extract_manifest),
+/// seeded with this crate's own v1/v2/v3 formats. Grows as embedders call
+/// [`register_format`]; never shrinks.
+fn registry() -> &'static Mutex<Vec<Box<dyn WrapperFormat + Send + Sync>>> {
+    static REGISTRY: OnceLock<Mutex<Vec<Box<dyn WrapperFormat + Send + Sync>>>> = OnceLock::new();
+    REGISTRY.get_or_init(|| {
+        Mutex::new(vec![
+            Box::new(V1Format) as Box<dyn WrapperFormat + Send + Sync>,
+            Box::new(V2Format),
+            Box::new(V3Format),
+        ])
+    })
+}
+
+/// Register a [`WrapperFormat`] so [`extract_manifest`](crate::extract_manifest)
+/// can dispatch to it by version byte, without touching its scan loop.
+///
+/// If `version` is already registered, the new format is tried first.
+pub fn register_format(format: Box<dyn WrapperFormat + Send + Sync>) {
+    registry().lock().unwrap().insert(0, format);
+}
+
+/// Run `f` against the registered reader for `version`, if one is
+/// registered.
+///
+/// Used by [`extract_manifest`](crate::extract_manifest) to dispatch on the
+/// version byte found right after MAGIC; embedders add their own formats via
+/// [`register_format`] instead of duplicating this lookup.
+pub fn with_reader_for_version<R>(
+    version: u8,
+    f: impl FnOnce(&dyn WrapperReader) -> R,
+) -> Option<R> {
+    let formats = registry().lock().unwrap();
+    formats
+        .iter()
+        .find(|format| WrapperReader::version(format.as_ref()) == version)
+        .map(|format| f(format.as_ref()))
+}
+
+/// The format [`encode_wrapper`](crate::encode_wrapper) writes new wrappers
+/// with.
+pub fn current_creator() -> Box<dyn WrapperCreator> {
+    Box::new(V3Format)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_with_reader_for_version_dispatches_to_registered_format() {
+        assert_eq!(with_reader_for_version(VERSION_1, |r| r.version()), Some(VERSION_1));
+        assert_eq!(with_reader_for_version(VERSION_2, |r| r.version()), Some(VERSION_2));
+        assert_eq!(with_reader_for_version(VERSION, |r| r.version()), Some(VERSION));
+    }
+
+    #[test]
+    fn test_with_reader_for_version_unknown_is_none() {
+        assert_eq!(with_reader_for_version(0xFE, |r| r.version()), None);
+    }
+
+    #[test]
+    fn test_register_format_lets_extract_manifest_dispatch_without_touching_the_scan_loop() {
+        // A custom format, entirely self-contained (it doesn't lean on this
+        // module's shared `decode_with_resolver`/`resolve_header`, which
+        // only know this crate's own versions), registered by an embedder.
+        const CUSTOM_VERSION: u8 = 0xFD;
+
+        struct CustomFormat;
+
+        impl WrapperCreator for CustomFormat {
+            fn version(&self) -> u8 {
+                CUSTOM_VERSION
+            }
+
+            fn encode(&self, manifest: &[u8]) -> String {
+                let mut out = String::new();
+                out.push(ZWNBSP);
+                for &b in MAGIC {
+                    out.push(byte_to_vs(b));
+                }
+                out.push(byte_to_vs(CUSTOM_VERSION));
+                for &b in &(manifest.len() as u32).to_be_bytes() {
+                    out.push(byte_to_vs(b));
+                }
+                for &b in manifest {
+                    out.push(byte_to_vs(b));
+                }
+                out
+            }
+
+            fn header_len(&self, _manifest: &[u8]) -> usize {
+                MAGIC_AND_VERSION_SIZE + FIXED_LENGTH_FIELD_SIZE
+            }
+        }
+
+        impl WrapperReader for CustomFormat {
+            fn version(&self) -> u8 {
+                CUSTOM_VERSION
+            }
+
+            fn try_decode(&self, bytes: &[u8]) -> Result<DecodedWrapper, Error> {
+                let header_len = MAGIC_AND_VERSION_SIZE + FIXED_LENGTH_FIELD_SIZE;
+                if bytes.len() < header_len
+                    || bytes[0..8] != *MAGIC
+                    || bytes[8] != CUSTOM_VERSION
+                {
+                    return Err(Error::InvalidMagic);
+                }
+                let declared_length = u32::from_be_bytes(
+                    bytes[MAGIC_AND_VERSION_SIZE..header_len].try_into().unwrap(),
+                ) as usize;
+                if bytes.len() < header_len + declared_length {
+                    return Err(Error::Truncated);
+                }
+                Ok(DecodedWrapper {
+                    manifest: bytes[header_len..header_len + declared_length].to_vec(),
+                    len_written: header_len + declared_length,
+                })
+            }
+        }
+
+        register_format(Box::new(CustomFormat));
+
+        let wrapper = CustomFormat.encode(b"custom format body");
+        let text = format!("before{}after", wrapper);
+        let result = crate::extract_manifest(&text).unwrap();
+        assert_eq!(result.manifest, Some(b"custom format body".to_vec()));
+        assert_eq!(result.clean_text, "beforeafter");
+    }
+
+    #[test]
+    fn test_each_format_encode_decode_roundtrips() {
+        let manifest = b"roundtrip manifest body";
+        for creator in [
+            Box::new(V1Format) as Box<dyn WrapperCreator>,
+            Box::new(V2Format),
+            Box::new(V3Format),
+        ] {
+            let wrapper = creator.encode(manifest);
+            // Strip the leading ZWNBSP and convert variation selectors back
+            // to bytes, mirroring what extract_manifest's scan loop does.
+            let bytes: Vec<u8> = wrapper.chars().skip(1).map(|c| crate::vs_to_byte(c).unwrap()).collect();
+            let decoded =
+                with_reader_for_version(creator.version(), |reader| reader.try_decode(&bytes))
+                    .unwrap()
+                    .unwrap();
+            assert_eq!(decoded.manifest, manifest);
+            assert_eq!(decoded.len_written, bytes.len());
+        }
+    }
+}
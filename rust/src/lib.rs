@@ -12,7 +12,11 @@
 use std::char;
 use unicode_normalization::UnicodeNormalization;
 
+pub mod decoder;
+pub mod format;
 pub mod validator;
+pub use decoder::{DecodeState, WrapperDecoder};
+pub use format::{DecodedWrapper, WrapperCreator, WrapperFormat, WrapperReader};
 pub use validator::{
     validate_jumbf_structure, validate_manifest, validate_wrapper_bytes,
     ValidationCode, ValidationIssue, ValidationResult,
@@ -21,10 +25,160 @@ pub use validator::{
 // ---------------------- Constants -------------------------------------------
 
 const MAGIC: &[u8; 8] = b"C2PATXT\0";
-const VERSION: u8 = 1;
-const HEADER_SIZE: usize = 13; // 8 (Magic) + 1 (Version) + 4 (Length)
+const VERSION: u8 = 3;
+const VERSION_1: u8 = 1;
+const VERSION_2: u8 = 2;
+const FIXED_LENGTH_FIELD_SIZE: usize = 4;
+const CHECKSUM_FIELD_SIZE: usize = 2;
+const MAGIC_AND_VERSION_SIZE: usize = 9; // 8 (Magic) + 1 (Version)
 const ZWNBSP: char = '\u{feff}';
 
+/// Maximum supported width, in bytes, of the v3 EBML-style varint length
+/// field. 5 bytes (35 value bits) comfortably covers any `u32` length.
+const VARINT_MAX_WIDTH: u32 = 5;
+
+/// Outcome of trying to work out a wrapper's header layout from the bytes
+/// seen so far, starting right after the MAGIC + VERSION bytes. Shared by
+/// the buffered ([`extract_manifest`]) and incremental
+/// ([`decoder::WrapperDecoder`]) scanners, and by [`validator::validate_wrapper_bytes`].
+pub(crate) enum HeaderLayout {
+    /// Not enough bytes yet to tell; feed/read more before deciding.
+    Incomplete,
+    /// The version byte, or the length field itself, is malformed.
+    Invalid,
+    /// The full header size (MAGIC through the checksum, inclusive of
+    /// `MAGIC_AND_VERSION_SIZE`) and declared body length are known.
+    Known {
+        header_size: usize,
+        declared_length: u32,
+    },
+}
+
+/// Work out the header layout for `version`, given the bytes observed right
+/// after the VERSION byte (i.e. starting at the length field).
+///
+/// - v1: 4-byte big-endian length, no checksum.
+/// - v2: 4-byte big-endian length, then a 2-byte checksum.
+/// - v3 (current): an EBML-style [`decode_varint`] length, then a 2-byte
+///   checksum.
+pub(crate) fn resolve_header(version: u8, after_version: &[u8]) -> HeaderLayout {
+    match version {
+        VERSION_1 => {
+            if after_version.len() < FIXED_LENGTH_FIELD_SIZE {
+                return HeaderLayout::Incomplete;
+            }
+            let declared_length = u32::from_be_bytes(after_version[0..4].try_into().unwrap());
+            HeaderLayout::Known {
+                header_size: MAGIC_AND_VERSION_SIZE + FIXED_LENGTH_FIELD_SIZE,
+                declared_length,
+            }
+        }
+        VERSION_2 => {
+            if after_version.len() < FIXED_LENGTH_FIELD_SIZE {
+                return HeaderLayout::Incomplete;
+            }
+            let declared_length = u32::from_be_bytes(after_version[0..4].try_into().unwrap());
+            HeaderLayout::Known {
+                header_size: MAGIC_AND_VERSION_SIZE + FIXED_LENGTH_FIELD_SIZE + CHECKSUM_FIELD_SIZE,
+                declared_length,
+            }
+        }
+        VERSION => match decode_varint(after_version) {
+            Some((declared_length, width)) => HeaderLayout::Known {
+                header_size: MAGIC_AND_VERSION_SIZE + width + CHECKSUM_FIELD_SIZE,
+                declared_length,
+            },
+            None => match after_version.first() {
+                None => HeaderLayout::Incomplete,
+                Some(0) => HeaderLayout::Invalid,
+                Some(&first) => {
+                    let width = first.leading_zeros() + 1;
+                    if width > VARINT_MAX_WIDTH {
+                        HeaderLayout::Invalid
+                    } else if (after_version.len() as u32) < width {
+                        HeaderLayout::Incomplete
+                    } else {
+                        // Enough bytes for the declared width, but
+                        // `decode_varint` still rejected it (reserved
+                        // all-bits-set pattern, or a value too large for
+                        // `u32`).
+                        HeaderLayout::Invalid
+                    }
+                }
+            },
+        },
+        _ => HeaderLayout::Invalid,
+    }
+}
+
+/// Encode `len` as an EBML-style variable-length integer: the first byte's
+/// leading zero bits (before the first set "marker" bit) count the number of
+/// continuation bytes, and the bits of the first byte after the marker bit,
+/// concatenated with the continuation bytes big-endian, form the value. Never produces the reserved all-value-bits-set ("unknown length")
+/// pattern.
+pub(crate) fn encode_varint(len: u32) -> Vec<u8> {
+    let len = len as u64;
+    let mut width = 1u32;
+    while len > (1u64 << (7 * width)) - 2 {
+        width += 1;
+    }
+    let mut out = vec![0u8; width as usize];
+    let mut remaining = len;
+    for i in (0..width as usize).rev() {
+        out[i] = (remaining & 0xFF) as u8;
+        remaining >>= 8;
+    }
+    out[0] |= 1 << (8 - width);
+    out
+}
+
+/// Decode an EBML-style variable-length integer from the start of `bytes`,
+/// returning `(value, bytes_consumed)`. Returns `None` if `bytes` is shorter
+/// than the width the first byte declares, that width exceeds
+/// [`VARINT_MAX_WIDTH`], or the value is the reserved all-bits-set pattern.
+pub(crate) fn decode_varint(bytes: &[u8]) -> Option<(u32, usize)> {
+    let &first = bytes.first()?;
+    if first == 0 {
+        return None;
+    }
+    let width = first.leading_zeros() + 1;
+    if width > VARINT_MAX_WIDTH || (bytes.len() as u32) < width {
+        return None;
+    }
+    let width = width as usize;
+    let mask = (1u16 << (8 - width)) - 1;
+    let mut value = (first as u16 & mask) as u64;
+    for &b in &bytes[1..width] {
+        value = (value << 8) | b as u64;
+    }
+    let reserved = (1u64 << (7 * width as u32)) - 1;
+    if value == reserved {
+        return None;
+    }
+    u32::try_from(value).ok().map(|v| (v, width))
+}
+
+/// Internet-style (RFC 1071) 16-bit one's complement checksum over `bytes`.
+///
+/// Accumulates the body as successive big-endian 16-bit words, padding a
+/// trailing odd byte with `0x00`, folds carries back into the low 16 bits,
+/// and returns the one's complement of the result.
+pub(crate) fn checksum16(bytes: &[u8]) -> u16 {
+    let mut sum: u32 = 0;
+    let mut chunks = bytes.chunks_exact(2);
+    for chunk in &mut chunks {
+        sum += u16::from_be_bytes([chunk[0], chunk[1]]) as u32;
+        // Fold carries after every word: left unchecked, `sum` would
+        // overflow its `u32` accumulator on bodies over ~128KB.
+        sum = (sum & 0xFFFF) + (sum >> 16);
+    }
+    if let [last] = chunks.remainder() {
+        sum += u16::from_be_bytes([*last, 0x00]) as u32;
+        sum = (sum & 0xFFFF) + (sum >> 16);
+    }
+    !(sum as u16)
+}
+
 // Variation Selector Ranges
 const VS_START: u32 = 0xFE00;
 const VS_END: u32 = 0xFE0F;
@@ -40,6 +194,7 @@ pub enum Error {
     UnsupportedVersion,
     Truncated,
     MultipleWrappers,
+    ChecksumMismatch { expected: u16, found: u16 },
 }
 
 impl std::fmt::Display for Error {
@@ -52,13 +207,18 @@ impl std::fmt::Display for Error {
             Error::UnsupportedVersion => write!(f, "Unsupported version"),
             Error::Truncated => write!(f, "Wrapper truncated before end of manifest"),
             Error::MultipleWrappers => write!(f, "Multiple C2PA wrappers detected"),
+            Error::ChecksumMismatch { expected, found } => write!(
+                f,
+                "Checksum mismatch: header declares {:#06x}, computed {:#06x}",
+                expected, found
+            ),
         }
     }
 }
 
 impl std::error::Error for Error {}
 
-fn byte_to_vs(byte: u8) -> char {
+pub(crate) fn byte_to_vs(byte: u8) -> char {
     if byte <= 15 {
         char::from_u32(VS_START + byte as u32).unwrap()
     } else {
@@ -66,7 +226,7 @@ fn byte_to_vs(byte: u8) -> char {
     }
 }
 
-fn vs_to_byte(c: char) -> Option<u8> {
+pub(crate) fn vs_to_byte(c: char) -> Option<u8> {
     let code = c as u32;
     if code >= VS_START && code <= VS_END {
         Some((code - VS_START) as u8)
@@ -78,31 +238,14 @@ fn vs_to_byte(c: char) -> Option<u8> {
 }
 
 /// Encode raw bytes into a C2PA Text Manifest Wrapper string.
+///
+/// Delegates to the crate's current [`WrapperCreator`]
+/// ([`format::current_creator`]), which as of this version writes a v3
+/// header: MAGIC + VERSION, a [`decode_varint`]-compatible variable-length
+/// length field (cheaper than a fixed 4 bytes for small manifests), and a
+/// 2-byte checksum over the manifest body (see [`checksum16`]).
 pub fn encode_wrapper(manifest_bytes: &[u8]) -> String {
-    let len = manifest_bytes.len() as u32;
-    
-    // Estimate capacity: 1 (ZWNBSP) + HEADER_SIZE + len
-    let mut out = String::with_capacity(1 + HEADER_SIZE + manifest_bytes.len());
-    out.push(ZWNBSP);
-
-    // Encode Header
-    for &b in MAGIC {
-        out.push(byte_to_vs(b));
-    }
-    out.push(byte_to_vs(VERSION));
-    
-    // Length (Big Endian)
-    out.push(byte_to_vs(((len >> 24) & 0xFF) as u8));
-    out.push(byte_to_vs(((len >> 16) & 0xFF) as u8));
-    out.push(byte_to_vs(((len >> 8) & 0xFF) as u8));
-    out.push(byte_to_vs((len & 0xFF) as u8));
-
-    // Encode Body
-    for &b in manifest_bytes {
-        out.push(byte_to_vs(b));
-    }
-
-    out
+    format::current_creator().encode(manifest_bytes)
 }
 
 /// Embed a C2PA manifest into text.
@@ -152,21 +295,15 @@ pub fn extract_manifest(text: &str) -> Result<ExtractionResult, Error> {
                 }
             }
 
-            // Check header if we have enough bytes
-            if current_bytes.len() >= HEADER_SIZE {
-                // Check Magic
-                if &current_bytes[0..8] == MAGIC {
-                    // Check Version
-                    if current_bytes[8] == VERSION {
-                        // Check Length
-                        let len = u32::from_be_bytes([
-                            current_bytes[9],
-                            current_bytes[10],
-                            current_bytes[11],
-                            current_bytes[12],
-                        ]) as usize;
-
-                        if current_bytes.len() >= HEADER_SIZE + len {
+            // Check header if we have enough bytes for MAGIC + VERSION, then
+            // dispatch to whichever registered format reads that version.
+            if current_bytes.len() >= MAGIC_AND_VERSION_SIZE && &current_bytes[0..8] == MAGIC {
+                let version = current_bytes[8];
+                if let Some(decode_result) =
+                    format::with_reader_for_version(version, |reader| reader.try_decode(&current_bytes))
+                {
+                    match decode_result {
+                        Ok(decoded) => {
                             // Found valid wrapper
                             if wrapper_start.is_some() {
                                 return Err(Error::MultipleWrappers);
@@ -178,14 +315,19 @@ pub fn extract_manifest(text: &str) -> Result<ExtractionResult, Error> {
                             } else {
                                 wrapper_end = Some(text.len());
                             }
-                            
-                            decoded_bytes = current_bytes[HEADER_SIZE..HEADER_SIZE + len].to_vec();
-                            
+
+                            decoded_bytes = decoded.manifest;
+
                             // We found one, but spec says we must ensure no others exist.
                             // Continue searching from j
                             i = j;
                             continue;
                         }
+                        Err(err @ Error::ChecksumMismatch { .. }) => return Err(err),
+                        Err(_) => {
+                            // Not a complete/valid wrapper of this format;
+                            // keep scanning past the ZWNBSP as plain text.
+                        }
                     }
                 }
             }
@@ -212,3 +354,55 @@ pub fn extract_manifest(text: &str) -> Result<ExtractionResult, Error> {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_checksum16_is_deterministic_and_detects_corruption() {
+        let body = b"hello c2pa manifest body";
+        let checksum = checksum16(body);
+        assert_eq!(checksum16(body), checksum, "checksum must be deterministic");
+        let mut corrupted = body.to_vec();
+        corrupted[3] ^= 0x01;
+        assert_ne!(checksum16(&corrupted), checksum);
+    }
+
+    #[test]
+    fn test_checksum16_odd_length_pads_trailing_byte() {
+        // An odd-length body exercises the `chunks.remainder()` padding path.
+        assert_ne!(checksum16(b"odd"), checksum16(b"od"));
+    }
+
+    #[test]
+    fn test_checksum16_does_not_overflow_on_large_body() {
+        // Regression test: a >128KB body of `0xFF` bytes used to overflow the
+        // `u32` accumulator when carries were only folded once at the end.
+        let body = vec![0xFFu8; 200_000];
+        let _ = checksum16(&body); // must not panic
+    }
+
+    #[test]
+    fn test_varint_roundtrip_boundary_widths() {
+        for len in [0u32, 1, 126, 127, 128, 16383, 16384, 2_097_151, 2_097_152] {
+            let encoded = encode_varint(len);
+            let (decoded, width) = decode_varint(&encoded).expect("should decode");
+            assert_eq!(decoded, len);
+            assert_eq!(width, encoded.len());
+        }
+    }
+
+    #[test]
+    fn test_varint_rejects_reserved_all_bits_set() {
+        // 1-byte width (marker bit 0x80) with all 7 value bits set (0xFF) is
+        // the reserved "unknown length" pattern and must be rejected.
+        assert!(decode_varint(&[0xFF]).is_none());
+    }
+
+    #[test]
+    fn test_varint_incomplete_bytes() {
+        // First byte declares a 2-byte width, but only 1 byte is available.
+        assert!(decode_varint(&[0b0100_0000]).is_none());
+    }
+}
@@ -13,6 +13,21 @@ const C2PA_MANIFEST_STORE_UUID: [u8; 16] = [
     0x80, 0x00, 0x00, 0xAA, 0x00, 0x38, 0x9B, 0x71,
 ];
 
+/// Maximum recursion depth when walking a JUMBF box tree, guarding against
+/// malformed or cyclic structures.
+const MAX_JUMBF_DEPTH: usize = 32;
+
+/// A single parsed JUMBF box, with any children discovered by recursive
+/// descent into nested superboxes.
+#[derive(Debug, Clone)]
+pub struct JumbfBox {
+    pub box_type: [u8; 4],
+    pub offset: usize,
+    pub size: usize,
+    pub label: Option<String>,
+    pub children: Vec<JumbfBox>,
+}
+
 /// C2PA-compliant validation status codes for text manifests.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum ValidationCode {
@@ -25,6 +40,7 @@ pub enum ValidationCode {
     InvalidMagic,
     UnsupportedVersion,
     LengthMismatch,
+    ChecksumMismatch,
     EmptyManifest,
     /// JUMBF-level failures
     InvalidJumbfHeader,
@@ -32,6 +48,12 @@ pub enum ValidationCode {
     MissingDescriptionBox,
     InvalidC2paUuid,
     TruncatedJumbf,
+    /// A child box's declared size runs past its parent's declared size.
+    ChildExceedsParent,
+    /// Trailing bytes after the last child box aren't enough for a box header.
+    OrphanedBox,
+    /// The box tree recursed deeper than [`MAX_JUMBF_DEPTH`].
+    MaxDepthExceeded,
 }
 
 impl ValidationCode {
@@ -44,12 +66,16 @@ impl ValidationCode {
             ValidationCode::InvalidMagic => "manifest.text.invalidMagic",
             ValidationCode::UnsupportedVersion => "manifest.text.unsupportedVersion",
             ValidationCode::LengthMismatch => "manifest.text.lengthMismatch",
+            ValidationCode::ChecksumMismatch => "manifest.text.checksumMismatch",
             ValidationCode::EmptyManifest => "manifest.text.emptyManifest",
             ValidationCode::InvalidJumbfHeader => "manifest.jumbf.invalidHeader",
             ValidationCode::InvalidJumbfBoxSize => "manifest.jumbf.invalidBoxSize",
             ValidationCode::MissingDescriptionBox => "manifest.jumbf.missingDescriptionBox",
             ValidationCode::InvalidC2paUuid => "manifest.jumbf.invalidC2paUuid",
             ValidationCode::TruncatedJumbf => "manifest.jumbf.truncated",
+            ValidationCode::ChildExceedsParent => "manifest.jumbf.childExceedsParent",
+            ValidationCode::OrphanedBox => "manifest.jumbf.orphanedBox",
+            ValidationCode::MaxDepthExceeded => "manifest.jumbf.maxDepthExceeded",
         }
     }
 }
@@ -85,6 +111,9 @@ pub struct ValidationResult {
     pub version: Option<u8>,
     pub declared_length: Option<u32>,
     pub actual_length: Option<usize>,
+    /// The recursively-parsed JUMBF box tree, when structural parsing got
+    /// far enough to produce one.
+    pub box_tree: Option<JumbfBox>,
 }
 
 impl ValidationResult {
@@ -98,6 +127,7 @@ impl ValidationResult {
             version: None,
             declared_length: None,
             actual_length: None,
+            box_tree: None,
         }
     }
 
@@ -147,151 +177,276 @@ impl fmt::Display for ValidationResult {
     }
 }
 
-/// Validate basic JUMBF box structure.
-pub fn validate_jumbf_structure(jumbf_bytes: &[u8], strict: bool) -> ValidationResult {
-    let mut result = ValidationResult::new();
-    result.jumbf_bytes = Some(jumbf_bytes.to_vec());
+/// Read a box's size/type header, returning `(box_size, box_type, header_size)`.
+/// `box_size` is the raw declared size (0 = "extends to end", 1 = extended
+/// 64-bit size follows); `header_size` is 8, or 16 when an extended size field
+/// is present. Returns `None` if `bytes` is too short to hold a header.
+fn read_box_header(bytes: &[u8]) -> Option<(u32, [u8; 4], usize)> {
+    if bytes.len() < 8 {
+        return None;
+    }
+    let box_size = u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+    let mut box_type = [0u8; 4];
+    box_type.copy_from_slice(&bytes[4..8]);
+    let header_size = if box_size == 1 { 16 } else { 8 };
+    Some((box_size, box_type, header_size))
+}
 
-    if jumbf_bytes.is_empty() {
-        result.add_issue(
-            ValidationCode::EmptyManifest,
-            "JUMBF content is empty",
-            Some(0),
-            None,
-        );
-        return result;
+/// Parse a `jumd` description box's label. The payload starts with the
+/// 16-byte content-type UUID, then a toggles byte; toggles bit 1 means a
+/// null-terminated UTF-8 label follows the toggles.
+fn parse_jumd_label(payload: &[u8]) -> Option<String> {
+    let toggles = *payload.get(16)?;
+    let offset = 17;
+    if toggles & 0x02 == 0 || payload.len() <= offset {
+        return None;
     }
+    let rest = &payload[offset..];
+    let end = rest.iter().position(|&b| b == 0).unwrap_or(rest.len());
+    Some(String::from_utf8_lossy(&rest[..end]).into_owned())
+}
 
-    // Minimum JUMBF box: 8 bytes header (size + type)
-    if jumbf_bytes.len() < 8 {
-        result.add_issue(
-            ValidationCode::InvalidJumbfHeader,
-            format!(
-                "JUMBF too short for box header: {} bytes, minimum 8",
-                jumbf_bytes.len()
-            ),
-            Some(0),
-            None,
-        );
-        return result;
+/// Recursively parse one JUMBF box at `bytes`, descending into superbox
+/// (`jumb`) children. `base_offset` is `bytes`'s offset within the original
+/// buffer, used to report absolute offsets. Structural problems are pushed
+/// onto `issues` as they're found rather than aborting the whole walk: a
+/// superbox whose child fails to parse (or overruns it) is still returned
+/// with the children parsed before that one, but its later children are not
+/// parsed.
+fn parse_jumbf_box(
+    bytes: &[u8],
+    base_offset: usize,
+    depth: usize,
+    is_outermost: bool,
+    strict: bool,
+    issues: &mut Vec<ValidationIssue>,
+) -> Option<JumbfBox> {
+    if depth > MAX_JUMBF_DEPTH {
+        issues.push(ValidationIssue {
+            code: ValidationCode::MaxDepthExceeded,
+            message: format!("JUMBF box tree exceeds max depth of {}", MAX_JUMBF_DEPTH),
+            offset: Some(base_offset),
+            context: None,
+        });
+        return None;
     }
 
-    // Parse first box header
-    let box_size = u32::from_be_bytes([
-        jumbf_bytes[0],
-        jumbf_bytes[1],
-        jumbf_bytes[2],
-        jumbf_bytes[3],
-    ]);
-    let box_type = &jumbf_bytes[4..8];
-
-    // Validate box size
-    let (effective_size, header_size) = if box_size == 0 {
-        // Size 0 means "extends to end of file"
-        (jumbf_bytes.len(), 8)
-    } else if box_size == 1 {
+    let (box_size, box_type, header_size) = match read_box_header(bytes) {
+        Some(header) => header,
+        None => {
+            issues.push(ValidationIssue {
+                code: ValidationCode::InvalidJumbfHeader,
+                message: format!(
+                    "JUMBF too short for box header: {} bytes, minimum 8",
+                    bytes.len()
+                ),
+                offset: Some(base_offset),
+                context: None,
+            });
+            return None;
+        }
+    };
+
+    let effective_size = if box_size == 0 {
+        // Size 0 means "extends to end of file" - only legal for the outermost box.
+        if !is_outermost {
+            issues.push(ValidationIssue {
+                code: ValidationCode::InvalidJumbfBoxSize,
+                message: "Zero (\"extends to end\") box size is only valid for the outermost box"
+                    .to_string(),
+                offset: Some(base_offset),
+                context: None,
+            });
+            return None;
+        }
+        bytes.len()
+    } else if header_size == 16 {
         // Extended size (64-bit)
-        if jumbf_bytes.len() < 16 {
-            result.add_issue(
-                ValidationCode::TruncatedJumbf,
-                "Extended box size declared but not enough bytes for 64-bit size field",
-                Some(0),
-                None,
-            );
-            return result;
+        if bytes.len() < 16 {
+            issues.push(ValidationIssue {
+                code: ValidationCode::TruncatedJumbf,
+                message: "Extended box size declared but not enough bytes for 64-bit size field"
+                    .to_string(),
+                offset: Some(base_offset),
+                context: None,
+            });
+            return None;
+        }
+        let extended_size = u64::from_be_bytes(bytes[8..16].try_into().unwrap()) as usize;
+        if extended_size < header_size {
+            issues.push(ValidationIssue {
+                code: ValidationCode::InvalidJumbfBoxSize,
+                message: format!(
+                    "Invalid extended box size: {} (minimum is {})",
+                    extended_size, header_size
+                ),
+                offset: Some(base_offset),
+                context: None,
+            });
+            return None;
         }
-        let extended_size = u64::from_be_bytes([
-            jumbf_bytes[8],
-            jumbf_bytes[9],
-            jumbf_bytes[10],
-            jumbf_bytes[11],
-            jumbf_bytes[12],
-            jumbf_bytes[13],
-            jumbf_bytes[14],
-            jumbf_bytes[15],
-        ]) as usize;
-        (extended_size, 16)
+        extended_size
     } else if box_size < 8 {
-        result.add_issue(
-            ValidationCode::InvalidJumbfBoxSize,
-            format!("Invalid box size: {} (minimum is 8)", box_size),
-            Some(0),
-            None,
-        );
-        return result;
+        issues.push(ValidationIssue {
+            code: ValidationCode::InvalidJumbfBoxSize,
+            message: format!("Invalid box size: {} (minimum is 8)", box_size),
+            offset: Some(base_offset),
+            context: None,
+        });
+        return None;
     } else {
-        (box_size as usize, 8)
+        box_size as usize
     };
 
-    // Check if we have enough bytes
-    if jumbf_bytes.len() < effective_size {
-        result.add_issue(
-            ValidationCode::TruncatedJumbf,
-            format!(
+    if bytes.len() < effective_size {
+        issues.push(ValidationIssue {
+            code: ValidationCode::TruncatedJumbf,
+            message: format!(
                 "JUMBF truncated: declared size {}, actual {}",
                 effective_size,
-                jumbf_bytes.len()
+                bytes.len()
             ),
-            Some(0),
-            None,
-        );
-        return result;
+            offset: Some(base_offset),
+            context: None,
+        });
+        return None;
     }
 
-    // Check for JUMBF superbox type
-    if box_type != JUMBF_SUPERBOX_TYPE {
-        result.add_issue(
-            ValidationCode::InvalidJumbfHeader,
-            format!(
+    if is_outermost && box_type != *JUMBF_SUPERBOX_TYPE {
+        issues.push(ValidationIssue {
+            code: ValidationCode::InvalidJumbfHeader,
+            message: format!(
                 "Expected JUMBF superbox type 'jumb', got '{}'",
-                String::from_utf8_lossy(box_type)
+                String::from_utf8_lossy(&box_type)
             ),
-            Some(4),
-            Some(format!("box_type={:02x?}", box_type)),
-        );
-        return result;
+            offset: Some(base_offset + 4),
+            context: Some(format!("box_type={:02x?}", box_type)),
+        });
+        return None;
     }
 
-    if strict {
-        // Check for description box (jumd)
-        if jumbf_bytes.len() < header_size + 8 {
-            result.add_issue(
-                ValidationCode::MissingDescriptionBox,
-                "JUMBF superbox too short to contain description box",
-                Some(header_size),
-                None,
-            );
-            return result;
-        }
+    let payload = &bytes[header_size..effective_size];
+    let label = if box_type == *JUMBF_DESC_TYPE {
+        parse_jumd_label(payload)
+    } else {
+        None
+    };
 
-        let desc_type = &jumbf_bytes[header_size + 4..header_size + 8];
-        if desc_type != JUMBF_DESC_TYPE {
-            result.add_issue(
-                ValidationCode::MissingDescriptionBox,
-                format!(
-                    "Expected description box 'jumd', got '{}'",
-                    String::from_utf8_lossy(desc_type)
-                ),
-                Some(header_size + 4),
-                None,
-            );
-            return result;
+    let mut children = Vec::new();
+    if box_type == *JUMBF_SUPERBOX_TYPE {
+        let parent_payload_len = effective_size - header_size;
+        let mut consumed = 0usize;
+        let mut first_child = true;
+
+        while consumed < parent_payload_len {
+            let child_offset = base_offset + header_size + consumed;
+            let remaining = &bytes[header_size + consumed..];
+            let left_in_parent = parent_payload_len - consumed;
+
+            if left_in_parent < 8 {
+                issues.push(ValidationIssue {
+                    code: ValidationCode::OrphanedBox,
+                    message: format!(
+                        "{} orphaned byte(s) after last child box, not enough for a box header",
+                        left_in_parent
+                    ),
+                    offset: Some(child_offset),
+                    context: None,
+                });
+                break;
+            }
+
+            if strict && first_child {
+                let child_type = &remaining[4..8];
+                if child_type != JUMBF_DESC_TYPE {
+                    issues.push(ValidationIssue {
+                        code: ValidationCode::MissingDescriptionBox,
+                        message: format!(
+                            "Expected description box 'jumd' as first child, got '{}'",
+                            String::from_utf8_lossy(child_type)
+                        ),
+                        offset: Some(child_offset),
+                        context: None,
+                    });
+                }
+            }
+            first_child = false;
+
+            match parse_jumbf_box(remaining, child_offset, depth + 1, false, strict, issues) {
+                Some(child) => {
+                    let overruns_parent = consumed + child.size > parent_payload_len;
+                    consumed += child.size;
+                    children.push(child);
+                    if overruns_parent {
+                        issues.push(ValidationIssue {
+                            code: ValidationCode::ChildExceedsParent,
+                            message: format!(
+                                "Child box at offset {} extends past its parent's declared size ({} bytes)",
+                                child_offset, parent_payload_len
+                            ),
+                            offset: Some(child_offset),
+                            context: None,
+                        });
+                        break;
+                    }
+                }
+                None => break,
+            }
         }
+    }
 
-        // Check for C2PA UUID
-        let uuid_offset = header_size + 8;
-        if jumbf_bytes.len() >= uuid_offset + 16 {
-            let found_uuid = &jumbf_bytes[uuid_offset..uuid_offset + 16];
-            if found_uuid != C2PA_MANIFEST_STORE_UUID {
-                result.add_issue(
-                    ValidationCode::InvalidC2paUuid,
-                    "Invalid C2PA manifest store UUID",
-                    Some(uuid_offset),
-                    Some(format!(
-                        "expected={:02x?}, found={:02x?}",
-                        C2PA_MANIFEST_STORE_UUID, found_uuid
-                    )),
-                );
+    Some(JumbfBox {
+        box_type,
+        offset: base_offset,
+        size: effective_size,
+        label,
+        children,
+    })
+}
+
+/// Validate JUMBF box structure via full recursive descent into the box tree.
+pub fn validate_jumbf_structure(jumbf_bytes: &[u8], strict: bool) -> ValidationResult {
+    let mut result = ValidationResult::new();
+    result.jumbf_bytes = Some(jumbf_bytes.to_vec());
+
+    if jumbf_bytes.is_empty() {
+        result.add_issue(
+            ValidationCode::EmptyManifest,
+            "JUMBF content is empty",
+            Some(0),
+            None,
+        );
+        return result;
+    }
+
+    let mut issues = Vec::new();
+    let root = parse_jumbf_box(jumbf_bytes, 0, 0, true, strict, &mut issues);
+
+    if !issues.is_empty() {
+        result.valid = false;
+    }
+    result.issues.extend(issues);
+    result.box_tree = root;
+
+    if result.valid && strict {
+        // The generic recursive walk already checks that the root's first
+        // child is a `jumd` description box; additionally confirm it carries
+        // the C2PA manifest store UUID.
+        if let Some((_, _, header_size)) = read_box_header(jumbf_bytes) {
+            let uuid_offset = header_size + 8;
+            if jumbf_bytes.len() >= uuid_offset + 16 {
+                let found_uuid = &jumbf_bytes[uuid_offset..uuid_offset + 16];
+                if found_uuid != C2PA_MANIFEST_STORE_UUID {
+                    result.add_issue(
+                        ValidationCode::InvalidC2paUuid,
+                        "Invalid C2PA manifest store UUID",
+                        Some(uuid_offset),
+                        Some(format!(
+                            "expected={:02x?}, found={:02x?}",
+                            C2PA_MANIFEST_STORE_UUID, found_uuid
+                        )),
+                    );
+                }
             }
         }
     }
@@ -330,18 +485,24 @@ pub fn validate_manifest(manifest_bytes: &[u8], validate_jumbf: bool, strict: bo
 }
 
 /// Validate a pre-encoded C2PATextManifestWrapper.
+///
+/// Understands the legacy v1 header (fixed-width length, no checksum), the
+/// v2 header (fixed-width length plus a checksum), and the current v3
+/// header (checksum plus an EBML-style variable-width length).
 pub fn validate_wrapper_bytes(wrapper_bytes: &[u8]) -> ValidationResult {
-    use crate::{MAGIC, VERSION, HEADER_SIZE};
+    use crate::{
+        checksum16, HeaderLayout, MAGIC, MAGIC_AND_VERSION_SIZE, VERSION, VERSION_1,
+    };
 
     let mut result = ValidationResult::new();
 
-    if wrapper_bytes.len() < HEADER_SIZE {
+    if wrapper_bytes.len() < MAGIC_AND_VERSION_SIZE {
         result.add_issue(
             ValidationCode::CorruptedWrapper,
             format!(
                 "Wrapper too short: {} bytes, minimum {}",
                 wrapper_bytes.len(),
-                HEADER_SIZE
+                MAGIC_AND_VERSION_SIZE
             ),
             Some(0),
             None,
@@ -363,29 +524,60 @@ pub fn validate_wrapper_bytes(wrapper_bytes: &[u8]) -> ValidationResult {
         return result;
     }
 
-    // Check version
+    // Check version and resolve the header layout
     let version = wrapper_bytes[8];
     result.version = Some(version);
-    if version != VERSION {
+    let (header_size, declared_length) =
+        match crate::resolve_header(version, &wrapper_bytes[MAGIC_AND_VERSION_SIZE..]) {
+            HeaderLayout::Known {
+                header_size,
+                declared_length,
+            } => (header_size, declared_length),
+            HeaderLayout::Incomplete => {
+                result.add_issue(
+                    ValidationCode::CorruptedWrapper,
+                    "Wrapper too short to contain a complete length field",
+                    Some(MAGIC_AND_VERSION_SIZE),
+                    None,
+                );
+                return result;
+            }
+            HeaderLayout::Invalid if version == VERSION => {
+                result.add_issue(
+                    ValidationCode::CorruptedWrapper,
+                    "Malformed variable-length length field",
+                    Some(MAGIC_AND_VERSION_SIZE),
+                    None,
+                );
+                return result;
+            }
+            HeaderLayout::Invalid => {
+                result.add_issue(
+                    ValidationCode::UnsupportedVersion,
+                    format!("Unsupported version: {}, expected {}", version, VERSION),
+                    Some(8),
+                    None,
+                );
+                return result;
+            }
+        };
+    result.declared_length = Some(declared_length);
+
+    if wrapper_bytes.len() < header_size {
         result.add_issue(
-            ValidationCode::UnsupportedVersion,
-            format!("Unsupported version: {}, expected {}", version, VERSION),
-            Some(8),
+            ValidationCode::CorruptedWrapper,
+            format!(
+                "Wrapper too short: {} bytes, minimum {}",
+                wrapper_bytes.len(),
+                header_size
+            ),
+            Some(0),
             None,
         );
         return result;
     }
 
-    // Check length
-    let declared_length = u32::from_be_bytes([
-        wrapper_bytes[9],
-        wrapper_bytes[10],
-        wrapper_bytes[11],
-        wrapper_bytes[12],
-    ]);
-    result.declared_length = Some(declared_length);
-
-    let actual_jumbf_length = wrapper_bytes.len() - HEADER_SIZE;
+    let actual_jumbf_length = wrapper_bytes.len() - header_size;
     result.actual_length = Some(actual_jumbf_length);
 
     if declared_length as usize != actual_jumbf_length {
@@ -395,17 +587,39 @@ pub fn validate_wrapper_bytes(wrapper_bytes: &[u8]) -> ValidationResult {
                 "Length mismatch: declares {} bytes, actual {}",
                 declared_length, actual_jumbf_length
             ),
-            Some(9),
+            Some(MAGIC_AND_VERSION_SIZE),
             None,
         );
         return result;
     }
 
     // Validate JUMBF
-    let jumbf_bytes = &wrapper_bytes[HEADER_SIZE..];
+    let jumbf_bytes = &wrapper_bytes[header_size..];
     result.jumbf_bytes = Some(jumbf_bytes.to_vec());
     result.manifest_bytes = Some(jumbf_bytes.to_vec());
 
+    // Check checksum (v2+)
+    if version != VERSION_1 {
+        let checksum_offset = header_size - 2;
+        let declared_checksum = u16::from_be_bytes([
+            wrapper_bytes[checksum_offset],
+            wrapper_bytes[checksum_offset + 1],
+        ]);
+        let computed_checksum = checksum16(jumbf_bytes);
+        if declared_checksum != computed_checksum {
+            result.add_issue(
+                ValidationCode::ChecksumMismatch,
+                format!(
+                    "Checksum mismatch: header declares {:#06x}, computed {:#06x}",
+                    declared_checksum, computed_checksum
+                ),
+                Some(checksum_offset),
+                None,
+            );
+            return result;
+        }
+    }
+
     let jumbf_result = validate_jumbf_structure(jumbf_bytes, false);
     if !jumbf_result.valid {
         result.issues.extend(jumbf_result.issues);
@@ -452,4 +666,124 @@ mod tests {
         assert!(!result.valid);
         assert_eq!(result.primary_code(), ValidationCode::TruncatedJumbf);
     }
+
+    /// Builds a non-extended box header: 4-byte size + 4-byte type.
+    fn box_header(size: u32, box_type: &[u8; 4]) -> Vec<u8> {
+        let mut out = size.to_be_bytes().to_vec();
+        out.extend_from_slice(box_type);
+        out
+    }
+
+    #[test]
+    fn test_recursive_tree_discovers_nested_children() {
+        // jumb(jumd, jumb(jumd))
+        let mut inner_desc = box_header(9, JUMBF_DESC_TYPE);
+        inner_desc.push(0x00); // too short for UUID + toggles: no label
+        let mut inner_superbox = box_header((8 + inner_desc.len()) as u32, JUMBF_SUPERBOX_TYPE);
+        inner_superbox.extend_from_slice(&inner_desc);
+
+        let mut outer_desc = box_header(9, JUMBF_DESC_TYPE);
+        outer_desc.push(0x00);
+
+        let mut root = box_header(
+            (8 + outer_desc.len() + inner_superbox.len()) as u32,
+            JUMBF_SUPERBOX_TYPE,
+        );
+        root.extend_from_slice(&outer_desc);
+        root.extend_from_slice(&inner_superbox);
+
+        let result = validate_jumbf_structure(&root, false);
+        assert!(result.valid, "{:?}", result.issues);
+        let tree = result.box_tree.expect("box tree should be populated");
+        assert_eq!(tree.children.len(), 2);
+        assert_eq!(tree.children[1].box_type, *JUMBF_SUPERBOX_TYPE);
+        assert_eq!(tree.children[1].children.len(), 1);
+    }
+
+    #[test]
+    fn test_extended_size_smaller_than_header_does_not_panic() {
+        // box_size == 1 (extended) but the 64-bit extended size (5) is
+        // smaller than the 16-byte extended header itself.
+        let mut malformed = vec![0, 0, 0, 1];
+        malformed.extend_from_slice(b"jumb");
+        malformed.extend_from_slice(&5u64.to_be_bytes());
+        malformed.extend_from_slice(b"padding-bytes-so-len-is-ok");
+
+        let result = validate_manifest(&malformed, true, false);
+        assert!(!result.valid);
+        assert_eq!(result.primary_code(), ValidationCode::InvalidJumbfBoxSize);
+    }
+
+    #[test]
+    fn test_child_exceeding_parent_is_flagged() {
+        // Root declares a payload region of 17 bytes (9-byte desc + 8 bytes
+        // of room), but the second child physically present afterwards is a
+        // full 16-byte box, so it both fits in the buffer (no truncation)
+        // and overruns the parent's declared boundary.
+        let mut desc = box_header(9, JUMBF_DESC_TYPE);
+        desc.push(0x00);
+        let mut child2 = box_header(16, JUMBF_DESC_TYPE);
+        child2.extend_from_slice(&[0u8; 8]);
+
+        let mut root = box_header(8 + 9 + 8, JUMBF_SUPERBOX_TYPE); // declares 17-byte payload
+        root.extend_from_slice(&desc);
+        root.extend_from_slice(&child2); // physically 16 bytes, overruns the declared 17-byte payload
+
+        let result = validate_jumbf_structure(&root, false);
+        assert!(!result.valid);
+        assert!(result
+            .issues
+            .iter()
+            .any(|i| i.code == ValidationCode::ChildExceedsParent));
+    }
+
+    #[test]
+    fn test_strict_mode_flags_missing_description_box_at_nested_level() {
+        // jumb(jumd, jumb(xxxx)) — the root's first child is a valid jumd,
+        // but the inner superbox's first (and only) child isn't, so strict
+        // mode must flag it even though it's one level down, not just at
+        // the root.
+        let inner_child = box_header(8, b"xxxx");
+        let mut inner_superbox =
+            box_header((8 + inner_child.len()) as u32, JUMBF_SUPERBOX_TYPE);
+        inner_superbox.extend_from_slice(&inner_child);
+
+        let mut outer_desc = box_header(9, JUMBF_DESC_TYPE);
+        outer_desc.push(0x00);
+
+        let mut root = box_header(
+            (8 + outer_desc.len() + inner_superbox.len()) as u32,
+            JUMBF_SUPERBOX_TYPE,
+        );
+        root.extend_from_slice(&outer_desc);
+        root.extend_from_slice(&inner_superbox);
+
+        let lenient = validate_jumbf_structure(&root, false);
+        assert!(lenient.valid, "{:?}", lenient.issues);
+
+        let strict = validate_jumbf_structure(&root, true);
+        assert!(!strict.valid);
+        assert!(strict
+            .issues
+            .iter()
+            .any(|i| i.code == ValidationCode::MissingDescriptionBox));
+    }
+
+    #[test]
+    fn test_max_depth_exceeded_does_not_recurse_forever() {
+        // Build a chain of nested superboxes deeper than MAX_JUMBF_DEPTH.
+        let mut innermost = box_header(8, JUMBF_SUPERBOX_TYPE);
+        for _ in 0..(MAX_JUMBF_DEPTH + 2) {
+            let mut wrapper = box_header((8 + innermost.len()) as u32, JUMBF_SUPERBOX_TYPE);
+            wrapper.extend_from_slice(&innermost);
+            innermost = wrapper;
+        }
+
+        let result = validate_jumbf_structure(&innermost, false);
+        assert!(!result.valid);
+        assert!(result
+            .issues
+            .iter()
+            .any(|i| i.code == ValidationCode::MaxDepthExceeded));
+    }
 }